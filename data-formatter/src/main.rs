@@ -3,6 +3,8 @@ use std::{fs::File, io::Write, num::ParseIntError, path::Path};
 use calamine::{open_workbook_auto, DataType, Range, Reader};
 use serde::Serialize;
 
+mod pdf;
+
 #[derive(Debug, Serialize)]
 struct Record {
     prefecture: Prefecture,
@@ -184,8 +186,232 @@ fn parse_roman_numerals(s: &str) -> Result<u8, MyError> {
     Ok(n)
 }
 
+/// Severity of a validation rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+enum Severity {
+    /// Reported, but tolerated unless `--strict` is set.
+    Warn,
+    /// Always treated as a hard error.
+    Deny,
+}
+
+/// Per-rule severities, overridable so callers can tune which anomalies are
+/// fatal.
+struct RuleSeverities {
+    dedicated_over_guaranteed: Severity,
+    phase_over_maximum: Severity,
+    extra_without_beds: Severity,
+}
+
+impl Default for RuleSeverities {
+    fn default() -> Self {
+        Self {
+            dedicated_over_guaranteed: Severity::Deny,
+            phase_over_maximum: Severity::Deny,
+            extra_without_beds: Severity::Warn,
+        }
+    }
+}
+
+/// A single semantic anomaly found in an otherwise parseable record.
+#[derive(Debug, Serialize)]
+struct Diagnostic {
+    prefecture: String,
+    rule: &'static str,
+    severity: Severity,
+    message: String,
+}
+
+/// Check records for semantically suspicious-but-parseable values that the
+/// spreadsheet layout does not rule out, such as more dedicated inpatients
+/// than guaranteed beds or a current phase above the maximum.
+fn validate(records: &[Record], severities: &RuleSeverities) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for r in records {
+        let pref = format!("{} {}", r.prefecture.code, r.prefecture.name);
+
+        if r.inpatient_count.dedicated > r.dedicated_bed_count.guaranteed {
+            diagnostics.push(Diagnostic {
+                prefecture: pref.clone(),
+                rule: "dedicated_over_guaranteed",
+                severity: severities.dedicated_over_guaranteed,
+                message: format!(
+                    "inpatient_count.dedicated ({}) exceeds dedicated_bed_count.guaranteed ({})",
+                    r.inpatient_count.dedicated, r.dedicated_bed_count.guaranteed
+                ),
+            });
+        }
+
+        if r.phase.current > r.phase.maximum {
+            diagnostics.push(Diagnostic {
+                prefecture: pref.clone(),
+                rule: "phase_over_maximum",
+                severity: severities.phase_over_maximum,
+                message: format!(
+                    "phase.current ({}) exceeds phase.maximum ({})",
+                    r.phase.current, r.phase.maximum
+                ),
+            });
+        }
+
+        if r.inpatient_count.extra > 0 && r.dedicated_bed_count.extra_guaranteed == 0 {
+            diagnostics.push(Diagnostic {
+                prefecture: pref.clone(),
+                rule: "extra_without_beds",
+                severity: severities.extra_without_beds,
+                message: format!(
+                    "inpatient_count.extra ({}) with zero extra_guaranteed beds",
+                    r.inpatient_count.extra
+                ),
+            });
+        }
+    }
+    diagnostics
+}
+
+/// Output serialization format selected by `--format`.
+enum Format {
+    /// Pretty-printed JSON array of nested records (the default).
+    Json,
+    /// Flat comma-separated values with a header row.
+    Csv,
+    /// Newline-delimited JSON, one flattened record per line.
+    Ndjson,
+}
+
+impl Format {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            "ndjson" => Some(Self::Ndjson),
+            _ => None,
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+        }
+    }
+}
+
+/// Column headers for the flattened (tabular) formats, in a fixed order.
+const COLUMNS: [&str; 11] = [
+    "prefecture.code",
+    "prefecture.name",
+    "phase.current",
+    "phase.maximum",
+    "phase.mode",
+    "inpatient.total",
+    "inpatient.dedicated",
+    "inpatient.extra",
+    "bed.available_or_assigned",
+    "bed.guaranteed",
+    "bed.extra_guaranteed",
+];
+
+/// Whether each [`COLUMNS`] entry carries a numeric count, parallel to
+/// `COLUMNS`. Numeric columns are emitted as JSON numbers in the ndjson
+/// machine format (prefecture codes stay strings to preserve leading zeros).
+const NUMERIC: [bool; 11] = [
+    false, false, true, true, false, true, true, true, true, true, true,
+];
+
+/// Render a `u32` count, optionally grouped with thousands separators (so
+/// `3066` becomes `3,066`) when emitting report-style output.
+fn render_count(value: u32, grouped: bool) -> String {
+    if grouped {
+        use num_format::{Locale, ToFormattedString};
+        value.to_formatted_string(&Locale::en)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Flatten a record into cell strings aligned with [`COLUMNS`].
+fn flatten(r: &Record, grouped: bool) -> Vec<String> {
+    let mode = match r.phase.mode {
+        PhaseMode::Normal => "Normal",
+        PhaseMode::Emergency => "Emergency",
+    };
+    vec![
+        r.prefecture.code.clone(),
+        r.prefecture.name.clone(),
+        r.phase.current.to_string(),
+        r.phase.maximum.to_string(),
+        mode.to_string(),
+        render_count(r.inpatient_count.total, grouped),
+        render_count(r.inpatient_count.dedicated, grouped),
+        render_count(r.inpatient_count.extra, grouped),
+        render_count(r.dedicated_bed_count.available_or_assigned, grouped),
+        render_count(r.dedicated_bed_count.guaranteed, grouped),
+        render_count(r.dedicated_bed_count.extra_guaranteed, grouped),
+    ]
+}
+
+/// Escape a CSV field, quoting when it contains a comma, quote, or newline.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Serialize records in the requested format.
+fn serialize(records: &[Record], format: &Format, grouped: bool) -> String {
+    match format {
+        Format::Json => serde_json::to_string_pretty(records).unwrap(),
+        Format::Csv => {
+            let mut out = String::new();
+            out.push_str(&COLUMNS.join(","));
+            out.push('\n');
+            for r in records {
+                let cells: Vec<String> = flatten(r, grouped).iter().map(|c| csv_field(c)).collect();
+                out.push_str(&cells.join(","));
+                out.push('\n');
+            }
+            out
+        }
+        Format::Ndjson => {
+            let mut out = String::new();
+            for r in records {
+                // Build the object in `COLUMNS` order so json/csv/ndjson agree
+                // on field ordering (a `serde_json::Map` would reorder keys).
+                out.push('{');
+                for (i, (k, v)) in COLUMNS.iter().zip(flatten(r, grouped)).enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    out.push_str(&serde_json::to_string(k).unwrap());
+                    out.push(':');
+                    // Numeric columns emit as JSON numbers so the ndjson feeds
+                    // log pipelines as data; the grouped report mode inserts
+                    // thousands separators, which are only valid as strings.
+                    if NUMERIC[i] && !grouped {
+                        out.push_str(&v);
+                    } else {
+                        out.push_str(&serde_json::to_string(&v).unwrap());
+                    }
+                }
+                out.push_str("}\n");
+            }
+            out
+        }
+    }
+}
+
 #[argopt::cmd]
-fn main(report_file: String, save_to: String) {
+fn main(
+    report_file: String,
+    save_to: String,
+    format: Option<String>,
+    grouped: bool,
+    strict: bool,
+) {
     let path = Path::new(&report_file);
     if !path.exists() {
         eprintln!("[error] {report_file}: File is not found.");
@@ -198,7 +424,29 @@ fn main(report_file: String, save_to: String) {
         return;
     }
 
-    let out_path = save_dir.join(path.with_extension("json").file_name().unwrap());
+    let format = match format.as_deref() {
+        None => Format::Json,
+        Some(s) => match Format::parse(s) {
+            Some(f) => f,
+            None => {
+                eprintln!("[error] {s}: unknown format (expected json, csv, or ndjson).");
+                return;
+            }
+        },
+    };
+
+    // `--grouped` only affects the flattened (csv/ndjson) cells; the JSON path
+    // serializes the structs directly and would ignore it silently.
+    if grouped && matches!(format, Format::Json) {
+        eprintln!("[error] --grouped has no effect with --format json (use csv or ndjson).");
+        return;
+    }
+
+    let out_path = save_dir.join(
+        path.with_extension(format.extension())
+            .file_name()
+            .unwrap(),
+    );
     if out_path.exists() {
         println!(
             "[warn] Skipped {}: File already exists.",
@@ -207,13 +455,44 @@ fn main(report_file: String, save_to: String) {
         return;
     }
 
-    let mut wb = open_workbook_auto(path).expect("Cannot open file.");
-    let (sheet_name, ws) = &wb.worksheets()[0];
-    println!("Extracting {sheet_name} sheet in {}...", path.display());
-    let records = collect_records(ws);
+    let records = match path.extension().and_then(|s| s.to_str()) {
+        Some("pdf") => {
+            println!("Extracting tables from {}...", path.display());
+            match std::fs::read(path).map_err(|e| e.to_string()).and_then(|b| {
+                pdf::collect_records(&b).map_err(|e| e.to_string())
+            }) {
+                Ok(records) => records,
+                Err(e) => {
+                    eprintln!("[error] {e}");
+                    return;
+                }
+            }
+        }
+        _ => {
+            let mut wb = open_workbook_auto(path).expect("Cannot open file.");
+            let (sheet_name, ws) = &wb.worksheets()[0];
+            println!("Extracting {sheet_name} sheet in {}...", path.display());
+            collect_records(ws)
+        }
+    };
+
+    let diagnostics = validate(&records, &RuleSeverities::default());
+    for d in &diagnostics {
+        eprintln!("[{:?}] {}: {}", d.severity, d.prefecture, d.message);
+    }
+    // `Deny` rules always fail; `--strict` additionally fails on warnings.
+    let fatal = diagnostics.iter().any(|d| {
+        d.severity == Severity::Deny || (strict && d.severity == Severity::Warn)
+    });
+    if fatal {
+        // Don't leave a serialized artifact that looks successful when the
+        // records failed validation.
+        eprintln!("[error] validation failed; no output written.");
+        std::process::exit(1);
+    }
 
     let mut file = File::create(out_path).unwrap();
-    file.write_all(serde_json::to_string_pretty(&records).unwrap().as_bytes())
+    file.write_all(serialize(&records, &format, grouped).as_bytes())
         .unwrap();
 
     println!("Done.");
@@ -0,0 +1,472 @@
+use std::num::ParseIntError;
+
+use pdf::content::{Operation, Primitive};
+use pdf::file::FileOptions;
+
+use crate::{
+    parse_phase, PatientCount, Prefecture, Record, ResourceCount, AVAILABLE_OR_ASSIGNED,
+    EXTRA_GUARANTEED, GUARANTEED, INPATIENT_DEDICATED, INPATIENT_EXTRA, INPATIENT_TOTAL,
+    PHASE_INFO, PREFECTURE_INFO,
+};
+
+/// Error raised while recovering [`Record`]s from a PDF report.
+#[derive(thiserror::Error, Debug)]
+pub enum PdfError {
+    #[error("pdf error")]
+    Pdf(#[from] pdf::PdfError),
+    #[error("could not detect the header row")]
+    HeaderNotFound,
+    #[error("missing cell for column {0} in row at y={1}")]
+    MissingCell(usize, i64),
+    #[error("non-integer value {0:?} in row at y={1}")]
+    NotANumber(String, i64),
+    #[error("parse int error")]
+    ParseInt(#[from] ParseIntError),
+    #[error("phase parse error")]
+    Phase(#[from] crate::MyError),
+}
+
+/// Text fragment recovered from the content stream together with its
+/// device-space position. `y` grows downward in reading order below.
+struct Fragment {
+    x: f64,
+    y: f64,
+    text: String,
+}
+
+/// Rows whose fragments share a `y` within this many points are treated as a
+/// single logical row. Column assignment does not use it except as the slack
+/// outside the header span when fewer than two header cells were detected.
+const MARGIN: f64 = 2.0;
+
+/// The header labels, in spreadsheet column order, used to anchor the
+/// x-position of each logical column.
+const HEADERS: [(&str, u32); 8] = [
+    ("都道府県", PREFECTURE_INFO),
+    ("入院者数", INPATIENT_TOTAL),
+    ("確保病床使用者数", INPATIENT_DEDICATED),
+    ("臨時等使用者数", INPATIENT_EXTRA),
+    ("フェーズ", PHASE_INFO),
+    ("即応病床数", AVAILABLE_OR_ASSIGNED),
+    ("確保病床数", GUARANTEED),
+    ("臨時等確保病床数", EXTRA_GUARANTEED),
+];
+
+/// Extract [`Record`]s from a PDF report positionally: every text fragment is
+/// collected with its coordinates, fragments are bucketed into rows by `y`,
+/// each row is sorted by `x`, and every fragment is matched to a logical
+/// column by the x-position of the detected header cell. The recovered cell
+/// strings are then fed through the same [`parse_phase`] / [`to_half_digits`]
+/// helpers as the xlsx path, preserving the prefecture-code ordering.
+///
+/// [`to_half_digits`]: util::to_half_digits
+pub fn collect_records(bytes: &[u8]) -> Result<Vec<Record>, PdfError> {
+    let mut records = Vec::new();
+    for page in read_fragments(bytes)? {
+        records.extend(assemble(page)?);
+    }
+    Ok(records)
+}
+
+/// Turn recovered text fragments into records: bucket into rows, detect the
+/// header, and read every subsequent data row.
+fn assemble(fragments: Vec<Fragment>) -> Result<Vec<Record>, PdfError> {
+    let rows = bucket_rows(fragments);
+
+    // Locate the header row: the first row carrying the prefecture label.
+    let header_idx = rows
+        .iter()
+        .position(|row| row.iter().any(|f| f.text.contains(HEADERS[0].0)))
+        .ok_or(PdfError::HeaderNotFound)?;
+
+    // Map each logical column to the x-position of its header cell.
+    let header = &rows[header_idx];
+    let mut columns: Vec<(u32, f64)> = Vec::new();
+    for (label, idx) in HEADERS {
+        if let Some(f) = header.iter().find(|f| f.text.contains(label)) {
+            columns.push((idx, f.x));
+        }
+    }
+
+    let mut records = Vec::new();
+    for row in &rows[header_idx + 1..] {
+        let y = row.first().map(|f| f.y.round() as i64).unwrap_or_default();
+        let cells = assign_cells(row, &columns);
+        // Skip non-data rows (footnotes, blanks) that lack a prefecture cell.
+        let Some(prefecture_cell) = cells.get(&PREFECTURE_INFO) else {
+            continue;
+        };
+        if prefecture_cell.trim().is_empty() {
+            continue;
+        }
+        records.push(read_record(&cells, y)?);
+    }
+
+    Ok(records)
+}
+
+/// Build a single [`Record`] from the column-indexed cell strings of one row,
+/// mirroring the xlsx `read_record`.
+fn read_record(
+    cells: &std::collections::BTreeMap<u32, String>,
+    y: i64,
+) -> Result<Record, PdfError> {
+    let cell = |column: u32| -> Result<&str, PdfError> {
+        cells
+            .get(&column)
+            .map(|s| s.trim())
+            .ok_or(PdfError::MissingCell(column as usize, y))
+    };
+
+    let prefecture = {
+        // The code and name may arrive as one fragment (`13 東京都`) or as two
+        // adjacent fragments concatenated without a space (`13東京都`), so split
+        // on the leading run of digits rather than on an ASCII space.
+        let raw = cell(PREFECTURE_INFO)?;
+        let split = raw
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(raw.len());
+        let code = raw[..split].to_string();
+        let name = raw[split..].trim().to_string();
+        Prefecture { code, name }
+    };
+    let phase = parse_phase(cell(PHASE_INFO)?)?;
+
+    let total = number(cell(INPATIENT_TOTAL)?, y)?;
+    let dedicated = number(cell(INPATIENT_DEDICATED)?, y)?;
+    let extra = number(cell(INPATIENT_EXTRA)?, y)?;
+    let available_or_assigned = number(cell(AVAILABLE_OR_ASSIGNED)?, y)?;
+    let guaranteed = number(cell(GUARANTEED)?, y)?;
+    let extra_guaranteed = number(cell(EXTRA_GUARANTEED)?, y)?;
+
+    Ok(Record {
+        prefecture,
+        phase,
+        inpatient_count: PatientCount {
+            total,
+            dedicated,
+            extra,
+        },
+        dedicated_bed_count: ResourceCount {
+            available_or_assigned,
+            guaranteed,
+            extra_guaranteed,
+        },
+    })
+}
+
+/// Parse a recovered cell string into a count, tolerating thousands separators
+/// and full-width digits via [`util::to_half_digits`].
+fn number(s: &str, y: i64) -> Result<u32, PdfError> {
+    let cleaned: String = s.chars().filter(|c| *c != ',' && !c.is_whitespace()).collect();
+    let half =
+        util::to_half_digits(&cleaned).ok_or_else(|| PdfError::NotANumber(s.to_string(), y))?;
+    Ok(half.parse()?)
+}
+
+/// Walk the content stream of each page, tracking the text matrix so each
+/// shown string is tagged with its `(x, y)` position. Fragments are returned
+/// per page: identical table layouts on different pages produce identical
+/// `y` values, so pages must be bucketed independently rather than merged into
+/// one coordinate space.
+fn read_fragments(bytes: &[u8]) -> Result<Vec<Vec<Fragment>>, PdfError> {
+    let file = FileOptions::cached().load(bytes)?;
+    let resolver = file.resolver();
+
+    let mut pages = Vec::new();
+    for page in file.pages() {
+        let page = page?;
+        let Some(content) = &page.contents else {
+            continue;
+        };
+
+        // Current text position; PDF `y` grows upward, so we negate it to keep
+        // reading order (topmost row first) when bucketing.
+        let (mut x, mut y) = (0.0_f64, 0.0_f64);
+        let mut leading = 0.0_f64;
+        let mut fragments = Vec::new();
+        for op in content.operations(&resolver)? {
+            show_fragment(&op, &mut x, &mut y, &mut leading, &mut fragments);
+        }
+        pages.push(fragments);
+    }
+
+    Ok(pages)
+}
+
+/// Apply a single content-stream operator, updating the running text position
+/// and pushing a [`Fragment`] for each text-showing operator.
+fn show_fragment(
+    op: &Operation,
+    x: &mut f64,
+    y: &mut f64,
+    leading: &mut f64,
+    fragments: &mut Vec<Fragment>,
+) {
+    let num = |p: &Primitive| -> f64 { p.as_number().unwrap_or(0.0) as f64 };
+
+    match op.operator.as_str() {
+        "Td" | "TD" => {
+            if let [dx, dy] = op.operands.as_slice() {
+                *x += num(dx);
+                *y += num(dy);
+                if op.operator == "TD" {
+                    *leading = -num(dy);
+                }
+            }
+        }
+        "Tm" => {
+            if let [_, _, _, _, tx, ty] = op.operands.as_slice() {
+                *x = num(tx);
+                *y = num(ty);
+            }
+        }
+        "TL" => {
+            if let [l] = op.operands.as_slice() {
+                *leading = num(l);
+            }
+        }
+        "T*" => {
+            *y -= *leading;
+        }
+        "Tj" | "'" | "\"" => {
+            if let Some(p) = op.operands.last() {
+                push_text(p, *x, *y, fragments);
+            }
+        }
+        "TJ" => {
+            if let Some(Primitive::Array(items)) = op.operands.first() {
+                for item in items {
+                    push_text(item, *x, *y, fragments);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Record a string primitive as a fragment, ignoring the numeric kerning
+/// adjustments that appear inside `TJ` arrays.
+fn push_text(p: &Primitive, x: f64, y: f64, fragments: &mut Vec<Fragment>) {
+    if let Primitive::String(s) = p {
+        if let Ok(text) = s.as_str() {
+            if !text.trim().is_empty() {
+                fragments.push(Fragment {
+                    x,
+                    y: -y,
+                    text: text.into_owned(),
+                });
+            }
+        }
+    }
+}
+
+/// Group fragments into rows by `y` (within [`MARGIN`]), returning rows in
+/// reading order with each row sorted left-to-right by `x`.
+fn bucket_rows(mut fragments: Vec<Fragment>) -> Vec<Vec<Fragment>> {
+    fragments.sort_by(|a, b| a.y.partial_cmp(&b.y).unwrap());
+
+    let mut rows: Vec<Vec<Fragment>> = Vec::new();
+    for f in fragments {
+        match rows.last_mut() {
+            Some(row) if (row[0].y - f.y).abs() <= MARGIN => row.push(f),
+            _ => rows.push(vec![f]),
+        }
+    }
+    for row in &mut rows {
+        row.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
+    }
+    rows
+}
+
+/// The tightest spacing between adjacent header cells, used to size the slack
+/// allowed outside the header span. Returns `None` when fewer than two columns
+/// were detected.
+fn min_column_gap(columns: &[(u32, f64)]) -> Option<f64> {
+    let mut xs: Vec<f64> = columns.iter().map(|&(_, x)| x).collect();
+    xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    xs.windows(2)
+        .map(|w| w[1] - w[0])
+        .fold(None, |acc, g| Some(acc.map_or(g, |m: f64| m.min(g))))
+}
+
+/// Assign each fragment in a row to the logical column it sits under, chosen by
+/// nearest header x (a Voronoi split at the midpoints between columns). Because
+/// numeric cells are right-aligned/centered under wide multi-byte headers, a
+/// cell's x legitimately drifts away from its header's left edge, so interior
+/// fragments are never dropped; only fragments outside the header span by more
+/// than one column's width (page numbers, footnotes, stray runs) are discarded.
+fn assign_cells(
+    row: &[Fragment],
+    columns: &[(u32, f64)],
+) -> std::collections::BTreeMap<u32, String> {
+    let slack = min_column_gap(columns).unwrap_or(MARGIN);
+    let lo = columns.iter().map(|&(_, x)| x).fold(f64::INFINITY, f64::min) - slack;
+    let hi = columns.iter().map(|&(_, x)| x).fold(f64::NEG_INFINITY, f64::max) + slack;
+
+    let mut cells: std::collections::BTreeMap<u32, String> = std::collections::BTreeMap::new();
+    for f in row {
+        if f.x < lo || f.x > hi {
+            continue;
+        }
+        let nearest = columns
+            .iter()
+            .min_by(|a, b| (a.1 - f.x).abs().partial_cmp(&(b.1 - f.x).abs()).unwrap());
+        if let Some(&(column, _)) = nearest {
+            cells.entry(column).or_default().push_str(&f.text);
+        }
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PhaseMode;
+
+    /// Column x-anchors matching the eight logical columns, 50pt apart.
+    const XS: [f64; 8] = [10.0, 60.0, 110.0, 160.0, 210.0, 260.0, 310.0, 360.0];
+
+    fn frag(x: f64, y: f64, text: &str) -> Fragment {
+        Fragment {
+            x,
+            y,
+            text: text.to_string(),
+        }
+    }
+
+    /// Header row followed by two prefecture rows, with a stray footnote run
+    /// planted in the first data row to exercise the gap-drop guard.
+    fn fixture() -> Vec<Fragment> {
+        let header = [
+            "都道府県",
+            "入院者数",
+            "確保病床使用者数",
+            "臨時等使用者数",
+            "フェーズ",
+            "即応病床数",
+            "確保病床数",
+            "臨時等確保病床数",
+        ];
+        let mut fragments = Vec::new();
+        for (x, label) in XS.iter().zip(header) {
+            fragments.push(frag(*x, 0.0, label));
+        }
+        // `13` and `東京都` rendered as two adjacent fragments under column 0.
+        let row1 = ["13", "3066", "2924", "225", "2／2", "5005", "7496", "579"];
+        for (x, cell) in XS.iter().zip(row1) {
+            fragments.push(frag(*x, 10.0, cell));
+        }
+        fragments.push(frag(12.0, 10.0, "東京都"));
+        // A footnote sitting in the gap past the last column must be dropped.
+        fragments.push(frag(500.0, 10.0, "※1"));
+
+        let row2 = ["06", "457", "151", "0", "Ⅰ／Ⅱ", "284", "284", "0"];
+        for (x, cell) in XS.iter().zip(row2) {
+            fragments.push(frag(*x, 20.0, cell));
+        }
+        fragments.push(frag(12.0, 20.0, "山形県"));
+        fragments
+    }
+
+    #[test]
+    fn test_prefecture_code_order() {
+        let records = assemble(fixture()).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].prefecture.code, "13");
+        assert_eq!(records[0].prefecture.name, "東京都");
+        assert_eq!(records[1].prefecture.code, "06");
+        // The footnote fragment must not have corrupted a real cell.
+        assert_eq!(records[0].inpatient_count.total, 3066);
+    }
+
+    #[test]
+    fn test_offset_cells_not_dropped() {
+        // Numeric cells rendered right of each header's left edge (as
+        // right-aligned columns are) must still land in their column rather
+        // than tripping `MissingCell`.
+        let header = [
+            "都道府県",
+            "入院者数",
+            "確保病床使用者数",
+            "臨時等使用者数",
+            "フェーズ",
+            "即応病床数",
+            "確保病床数",
+            "臨時等確保病床数",
+        ];
+        let mut fragments = Vec::new();
+        for (x, label) in XS.iter().zip(header) {
+            fragments.push(frag(*x, 0.0, label));
+        }
+        let cells = ["13", "3066", "2924", "225", "2／2", "5005", "7496", "579"];
+        for (x, cell) in XS.iter().zip(cells) {
+            fragments.push(frag(*x + 20.0, 10.0, cell));
+        }
+        fragments.push(frag(30.0, 10.0, "東京都"));
+        // Footnote well outside the header span is still dropped.
+        fragments.push(frag(600.0, 10.0, "※2"));
+
+        let records = assemble(fragments).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].prefecture.code, "13");
+        assert_eq!(records[0].inpatient_count.total, 3066);
+        assert_eq!(records[0].dedicated_bed_count.extra_guaranteed, 579);
+    }
+
+    /// A header row at `y = 0` followed by a single prefecture row at `y = 10`;
+    /// used to synthesize two pages sharing the same layout.
+    fn page(code: &str, name: &str, total: &str) -> Vec<Fragment> {
+        let header = [
+            "都道府県",
+            "入院者数",
+            "確保病床使用者数",
+            "臨時等使用者数",
+            "フェーズ",
+            "即応病床数",
+            "確保病床数",
+            "臨時等確保病床数",
+        ];
+        let mut fragments = Vec::new();
+        for (x, label) in XS.iter().zip(header) {
+            fragments.push(frag(*x, 0.0, label));
+        }
+        let cells = [code, total, "0", "0", "2／2", "0", "0", "0"];
+        for (x, cell) in XS.iter().zip(cells) {
+            fragments.push(frag(*x, 10.0, cell));
+        }
+        fragments.push(frag(12.0, 10.0, name));
+        fragments
+    }
+
+    #[test]
+    fn test_pages_bucketed_independently() {
+        // Two pages with identical layout share the same `y` values; bucketing
+        // them together would merge both prefectures' cells into one row. Each
+        // page must be assembled on its own.
+        let pages = [page("13", "東京都", "3066"), page("06", "山形県", "457")];
+        let mut records = Vec::new();
+        for p in pages {
+            records.extend(assemble(p).unwrap());
+        }
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].prefecture.code, "13");
+        assert_eq!(records[0].inpatient_count.total, 3066);
+        assert_eq!(records[1].prefecture.code, "06");
+        assert_eq!(records[1].inpatient_count.total, 457);
+    }
+
+    #[test]
+    fn test_read_known_row() {
+        let records = assemble(fixture()).unwrap();
+        let r = &records[0];
+        assert!(matches!(r.phase.mode, PhaseMode::Normal));
+        assert_eq!(r.phase.current, 2);
+        assert_eq!(r.phase.maximum, 2);
+        assert_eq!(r.inpatient_count.dedicated, 2924);
+        assert_eq!(r.inpatient_count.extra, 225);
+        assert_eq!(r.dedicated_bed_count.available_or_assigned, 5005);
+        assert_eq!(r.dedicated_bed_count.guaranteed, 7496);
+        assert_eq!(r.dedicated_bed_count.extra_guaranteed, 579);
+    }
+}
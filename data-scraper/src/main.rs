@@ -1,13 +1,68 @@
 use std::{
-    fs::File,
+    collections::{hash_map::DefaultHasher, HashMap},
+    fs::{self, File},
+    hash::{Hash, Hasher},
     io::{self, Write},
     path::Path,
+    sync::{Arc, Mutex},
 };
 
 use chrono::{DateTime, TimeZone};
 use chrono_tz::{Asia::Tokyo, Tz};
+use futures::stream::{self, StreamExt};
 use lazy_regex::regex;
 use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+
+/// Default number of reports fetched concurrently.
+const DEFAULT_CONCURRENCY: usize = 8;
+
+/// Name of the persisted manifest kept inside `save_to`.
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// A record of one successfully downloaded report, used to decide what to skip
+/// on a subsequent run. The index page is still fetched and parsed on every
+/// run; the manifest only decides which of its reports are downloaded again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ManifestEntry {
+    /// Source path on the MHLW site.
+    path: String,
+    /// Resolved report timestamp, in `%Y%m%dT%H%M%Z` form.
+    timestamp: String,
+    /// Output file name written under `save_to`.
+    filename: String,
+    /// Length of the written file in bytes.
+    length: u64,
+    /// Content hash of the written bytes.
+    hash: String,
+}
+
+/// The on-disk manifest, keyed by output file name.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    entries: HashMap<String, ManifestEntry>,
+}
+
+impl Manifest {
+    fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self).unwrap())
+    }
+}
+
+/// Content hash of a byte buffer. A fast, dependency-free digest is enough to
+/// catch truncated or corrupted transfers.
+fn content_hash(bytes: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
 
 #[derive(thiserror::Error, Debug)]
 enum MyError {
@@ -21,13 +76,13 @@ enum MyError {
 
 #[argopt::cmd]
 #[tokio::main]
-async fn main(save_to: String, n: Option<usize>) {
-    if let Err(e) = run(save_to, n).await {
+async fn main(save_to: String, n: Option<usize>, k: Option<usize>) {
+    if let Err(e) = run(save_to, n, k).await {
         println!("[error] {e}");
     }
 }
 
-async fn run(save_to: String, n: Option<usize>) -> Result<(), MyError> {
+async fn run(save_to: String, n: Option<usize>, k: Option<usize>) -> Result<(), MyError> {
     let dir = Path::new(&save_to);
     if !dir.exists() {
         return Err(MyError::DirNotFound(save_to));
@@ -38,31 +93,88 @@ async fn run(save_to: String, n: Option<usize>) -> Result<(), MyError> {
         .text()
         .await?;
 
-    for r in parse_html(&body, n) {
-        let filename = {
-            let name = r.timestamp.format("%Y%m%dT%H%M%Z").to_string();
-            if let Some(ext) = Path::new(&r.path).extension().and_then(|s| s.to_str()) {
-                format!("{name}.{ext}")
-            } else {
-                name
+    let reports = parse_html(&body, n);
+    let k = k.unwrap_or(DEFAULT_CONCURRENCY).max(1);
+
+    let manifest_path = dir.join(MANIFEST_FILE);
+    let manifest = Arc::new(Mutex::new(Manifest::load(&manifest_path)));
+
+    // Fetch up to `k` reports at a time; ordering is irrelevant because every
+    // report writes to its own timestamped file.
+    stream::iter(reports)
+        .map(|r| fetch_report(dir, &manifest_path, manifest.clone(), r))
+        .buffer_unordered(k)
+        .for_each(|res| async {
+            if let Err(e) = res {
+                println!("[error] {e}");
             }
-        };
-        let data = reqwest::get(format!("https://www.mhlw.go.jp/{}", r.path))
-            .await?
-            .bytes()
-            .await?;
-        let path = dir.join(filename);
-        if path.exists() {
-            println!("[warn] file {} already exists.", path.display());
-            continue;
-        }
-        let mut file = File::create(path)?;
-        file.write_all(&data)?;
-        println!(
-            "[info] report on {} are exported.",
-            r.timestamp.format("%Y-%m-%d %H:%M %Z")
-        );
+        })
+        .await;
+
+    Ok(())
+}
+
+fn report_filename(r: &Report) -> String {
+    let name = r.timestamp.format("%Y%m%dT%H%M%Z").to_string();
+    if let Some(ext) = Path::new(&r.path).extension().and_then(|s| s.to_str()) {
+        format!("{name}.{ext}")
+    } else {
+        name
+    }
+}
+
+/// Whether the manifest already records this output file as present and its
+/// bytes still match the recorded size and hash.
+fn is_verified(dir: &Path, manifest: &Manifest, filename: &str) -> bool {
+    let Some(entry) = manifest.entries.get(filename) else {
+        return false;
+    };
+    let Ok(bytes) = fs::read(dir.join(filename)) else {
+        return false;
+    };
+    bytes.len() as u64 == entry.length && content_hash(&bytes) == entry.hash
+}
+
+async fn fetch_report(
+    dir: &Path,
+    manifest_path: &Path,
+    manifest: Arc<Mutex<Manifest>>,
+    r: Report,
+) -> Result<(), MyError> {
+    let filename = report_filename(&r);
+
+    if is_verified(dir, &manifest.lock().unwrap(), &filename) {
+        println!("[warn] file {filename} already present and verified.");
+        return Ok(());
     }
+
+    let data = reqwest::get(format!("https://www.mhlw.go.jp/{}", r.path))
+        .await?
+        .bytes()
+        .await?;
+
+    // Only touch the output file once the whole body is in hand, so an
+    // interrupted transfer leaves no partial file to be mistaken for complete.
+    let mut file = File::create(dir.join(&filename))?;
+    file.write_all(&data)?;
+
+    let entry = ManifestEntry {
+        path: r.path.clone(),
+        timestamp: r.timestamp.format("%Y%m%dT%H%M%Z").to_string(),
+        filename: filename.clone(),
+        length: data.len() as u64,
+        hash: content_hash(&data),
+    };
+    {
+        let mut manifest = manifest.lock().unwrap();
+        manifest.entries.insert(filename, entry);
+        manifest.save(manifest_path)?;
+    }
+
+    println!(
+        "[info] report on {} are exported.",
+        r.timestamp.format("%Y-%m-%d %H:%M %Z")
+    );
     Ok(())
 }
 
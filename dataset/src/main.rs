@@ -0,0 +1,200 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+};
+
+use chrono::TimeZone;
+use chrono_tz::{Asia::Tokyo, Tz};
+use serde::{Deserialize, Serialize};
+
+#[derive(thiserror::Error, Debug)]
+enum MyError {
+    #[error("{0}: No such directory")]
+    DirNotFound(String),
+    #[error("system file io error")]
+    IoError(#[from] io::Error),
+    #[error("cannot parse {0} as a report json")]
+    ParseError(String),
+}
+
+/// A single parsed report, mirroring `data-formatter`'s `Record` on the
+/// deserialization side.
+#[derive(Debug, Deserialize)]
+struct Record {
+    prefecture: Prefecture,
+    phase: Phase,
+    inpatient_count: PatientCount,
+    dedicated_bed_count: ResourceCount,
+}
+
+#[derive(Debug, Deserialize)]
+struct Prefecture {
+    code: String,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Phase {
+    current: u8,
+    maximum: u8,
+}
+
+#[derive(Debug, Deserialize)]
+struct PatientCount {
+    total: u32,
+    dedicated: u32,
+    extra: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResourceCount {
+    available_or_assigned: u32,
+    guaranteed: u32,
+    extra_guaranteed: u32,
+}
+
+/// A tidy long-format dataset: one row per `(timestamp, prefecture, field)`
+/// observation, with explicit `columns` headers.
+#[derive(Debug, Serialize)]
+struct DataSet {
+    columns: Vec<&'static str>,
+    rows: Vec<Row>,
+}
+
+#[derive(Debug, Serialize)]
+struct Row {
+    timestamp: String,
+    prefecture_code: String,
+    prefecture_name: String,
+    field: &'static str,
+    value: u32,
+}
+
+impl DataSet {
+    fn new() -> Self {
+        Self {
+            columns: vec![
+                "timestamp",
+                "prefecture_code",
+                "prefecture_name",
+                "field",
+                "value",
+            ],
+            rows: Vec::new(),
+        }
+    }
+
+    /// Fold one report's records into the dataset, emitting one row per
+    /// measure so downstream tooling can pivot on prefecture code and time.
+    fn extend(&mut self, timestamp: &str, records: Vec<Record>) {
+        for r in records {
+            let measures = [
+                ("phase.current", r.phase.current as u32),
+                ("phase.maximum", r.phase.maximum as u32),
+                ("inpatient.total", r.inpatient_count.total),
+                ("inpatient.dedicated", r.inpatient_count.dedicated),
+                ("inpatient.extra", r.inpatient_count.extra),
+                (
+                    "bed.available_or_assigned",
+                    r.dedicated_bed_count.available_or_assigned,
+                ),
+                ("bed.guaranteed", r.dedicated_bed_count.guaranteed),
+                ("bed.extra_guaranteed", r.dedicated_bed_count.extra_guaranteed),
+            ];
+            for (field, value) in measures {
+                self.rows.push(Row {
+                    timestamp: timestamp.to_string(),
+                    prefecture_code: r.prefecture.code.clone(),
+                    prefecture_name: r.prefecture.name.clone(),
+                    field,
+                    value,
+                });
+            }
+        }
+    }
+
+    /// Write the dataset as a tab-separated flat file with a header line.
+    fn write_flat(&self, mut w: impl Write) -> io::Result<()> {
+        writeln!(w, "{}", self.columns.join("\t"))?;
+        for row in &self.rows {
+            writeln!(
+                w,
+                "{}\t{}\t{}\t{}\t{}",
+                row.timestamp, row.prefecture_code, row.prefecture_name, row.field, row.value
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Recover the report timestamp encoded in a json file name, using the same
+/// `%Y%m%dT%H%M%Z` layout `data-scraper` writes its report file names in.
+fn parse_timestamp(stem: &str) -> Option<chrono::DateTime<Tz>> {
+    Tokyo.datetime_from_str(stem, "%Y%m%dT%H%M%Z").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_timestamp;
+
+    #[test]
+    fn test_parse_timestamp() {
+        let dt = parse_timestamp("20221130T0000JST").unwrap();
+        assert_eq!(dt.format("%Y-%m-%d %H:%M").to_string(), "2022-11-30 00:00");
+
+        let dt = parse_timestamp("20220905T0030JST").unwrap();
+        assert_eq!(dt.format("%Y-%m-%d %H:%M").to_string(), "2022-09-05 00:30");
+
+        assert!(parse_timestamp("not-a-timestamp").is_none());
+    }
+}
+
+#[argopt::cmd]
+fn main(input_dir: String, save_to: String) {
+    if let Err(e) = run(input_dir, save_to) {
+        eprintln!("[error] {e}");
+    }
+}
+
+fn run(input_dir: String, save_to: String) -> Result<(), MyError> {
+    let dir = Path::new(&input_dir);
+    if !dir.is_dir() {
+        return Err(MyError::DirNotFound(input_dir));
+    }
+
+    let mut dataset = DataSet::new();
+    let mut entries: Vec<_> = fs::read_dir(dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("json"))
+        .collect();
+    // Sort by file name so the resulting rows are chronologically grouped.
+    entries.sort();
+
+    for path in entries {
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+        let Some(dt) = parse_timestamp(stem) else {
+            println!("[warn] Skipped {}: not a timestamped report.", path.display());
+            continue;
+        };
+        let body = fs::read_to_string(&path)?;
+        let records: Vec<Record> = serde_json::from_str(&body)
+            .map_err(|_| MyError::ParseError(path.display().to_string()))?;
+        dataset.extend(&dt.to_rfc3339(), records);
+        println!("[info] merged {}.", path.display());
+    }
+
+    let out = Path::new(&save_to);
+    let json_path = out.with_extension("json");
+    File::create(&json_path)?.write_all(serde_json::to_string_pretty(&dataset).unwrap().as_bytes())?;
+    let flat_path = out.with_extension("tsv");
+    dataset.write_flat(File::create(&flat_path)?)?;
+    println!(
+        "[info] wrote {} rows to {} and {}.",
+        dataset.rows.len(),
+        json_path.display(),
+        flat_path.display()
+    );
+
+    Ok(())
+}